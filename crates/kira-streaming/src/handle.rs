@@ -1,10 +1,12 @@
 use std::{error::Error, fmt::Display, sync::Arc};
 
-use kira::{parameter::Tween, value::Value};
-use ringbuf::Producer;
+use kira::{parameter::Tween, sound::event::SoundEvent, value::Value};
+use ringbuf::{Consumer, Producer, RingBuffer};
 
 use crate::{sound::Shared, Command};
 
+const EVENT_CAPACITY: usize = 16;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CommandQueueFull;
 
@@ -16,16 +18,45 @@ impl Display for CommandQueueFull {
 
 impl Error for CommandQueueFull {}
 
+/// `StreamingSoundHandle` is a single-consumer handle: only one owner
+/// should call `pop_event` for a given sound, since each event only goes
+/// to whichever caller polls it first.
 pub struct StreamingSoundHandle {
 	pub(crate) shared: Arc<Shared>,
 	pub(crate) command_producer: Producer<Command>,
+	event_producer: Producer<SoundEvent>,
+	event_consumer: Consumer<SoundEvent>,
 }
 
 impl StreamingSoundHandle {
+	pub(crate) fn new(shared: Arc<Shared>, command_producer: Producer<Command>) -> Self {
+		let (event_producer, event_consumer) = RingBuffer::new(EVENT_CAPACITY).split();
+		Self {
+			shared,
+			command_producer,
+			event_producer,
+			event_consumer,
+		}
+	}
+
+	/// Pushes an event to this sound's event stream. The stream's producer
+	/// lives only in this handle, so the only events it carries are the
+	/// `Paused`, `Resumed` and `Stopped` pushed here; nothing on the
+	/// streaming thread pushes `Finished` or `Position` to it.
+	fn push_event(&mut self, event: SoundEvent) {
+		self.event_producer.push(event).ok();
+	}
+
 	pub fn position(&self) -> f64 {
 		self.shared.position()
 	}
 
+	/// Returns the next playback status event for this sound, if one has
+	/// been pushed since the last call to this method.
+	pub fn pop_event(&mut self) -> Option<SoundEvent> {
+		self.event_consumer.pop()
+	}
+
 	pub fn set_volume(&mut self, volume: impl Into<Value>) -> Result<(), CommandQueueFull> {
 		self.command_producer
 			.push(Command::SetVolume(volume.into()))
@@ -50,19 +81,25 @@ impl StreamingSoundHandle {
 	pub fn pause(&mut self, tween: Tween) -> Result<(), CommandQueueFull> {
 		self.command_producer
 			.push(Command::Pause(tween))
-			.map_err(|_| CommandQueueFull)
+			.map_err(|_| CommandQueueFull)?;
+		self.push_event(SoundEvent::Paused);
+		Ok(())
 	}
 
 	pub fn resume(&mut self, tween: Tween) -> Result<(), CommandQueueFull> {
 		self.command_producer
 			.push(Command::Resume(tween))
-			.map_err(|_| CommandQueueFull)
+			.map_err(|_| CommandQueueFull)?;
+		self.push_event(SoundEvent::Resumed);
+		Ok(())
 	}
 
 	pub fn stop(&mut self, tween: Tween) -> Result<(), CommandQueueFull> {
 		self.command_producer
 			.push(Command::Stop(tween))
-			.map_err(|_| CommandQueueFull)
+			.map_err(|_| CommandQueueFull)?;
+		self.push_event(SoundEvent::Stopped);
+		Ok(())
 	}
 
 	pub fn seek_to(&mut self, position: f64) -> Result<(), CommandQueueFull> {
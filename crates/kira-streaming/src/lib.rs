@@ -1,15 +1,17 @@
 mod data;
 mod handle;
+mod queue;
 mod settings;
 mod sound;
 
 pub use data::*;
 pub use handle::*;
+pub use queue::*;
 pub use settings::*;
 
 use std::collections::VecDeque;
 
-use kira::{dsp::Frame, parameter::Tween};
+use kira::{dsp::Frame, parameter::Tween, value::Value};
 
 pub trait Decoder: Send + Sync {
 	fn sample_rate(&mut self) -> u32;
@@ -21,7 +23,12 @@ pub trait Decoder: Send + Sync {
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Command {
+	SetVolume(Value),
+	SetPlaybackRate(Value),
+	SetPanning(Value),
 	Pause(Tween),
 	Resume(Tween),
 	Stop(Tween),
+	SeekTo(f64),
+	SeekBy(f64),
 }
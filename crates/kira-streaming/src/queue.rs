@@ -0,0 +1,460 @@
+//! Gapless playback of a sequence of streaming sounds.
+//!
+//! A [`SoundQueue`] owns one [`Decoder`] at a time plus however many are
+//! waiting to play next. As the currently playing decoder's buffered frames
+//! run low, the queue preloads the next decoder on the streaming thread so
+//! there's no silence (and optionally a short crossfade) between tracks.
+
+use std::collections::VecDeque;
+
+use kira::dsp::Frame;
+use ringbuf::{Consumer, Producer, RingBuffer};
+
+use crate::Decoder;
+
+const COMMAND_CAPACITY: usize = 16;
+const EVENT_CAPACITY: usize = 16;
+
+/// Settings for a [`SoundQueue`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueueSettings {
+	/// How far ahead of the end of the current track (in seconds of
+	/// decoded audio) the next track's decoder should be preloaded.
+	pub lookahead: f64,
+	/// The number of frames to crossfade between the end of one track
+	/// and the start of the next. A value of `0` disables crossfading.
+	pub crossfade_frames: usize,
+}
+
+impl QueueSettings {
+	pub fn new() -> Self {
+		Self {
+			lookahead: 0.5,
+			crossfade_frames: 0,
+		}
+	}
+
+	pub fn lookahead(self, lookahead: f64) -> Self {
+		Self { lookahead, ..self }
+	}
+
+	pub fn crossfade_frames(self, crossfade_frames: usize) -> Self {
+		Self {
+			crossfade_frames,
+			..self
+		}
+	}
+}
+
+impl Default for QueueSettings {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// A notification pushed from the queue's streaming thread as it works
+/// through its tracks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueEvent {
+	/// The queue preloaded (started decoding) the next track. This is pushed
+	/// while the current track is still playing on its own; the next track
+	/// is heard once the current one runs out or the crossfade begins.
+	NextTrack,
+	/// The queue has no more tracks to play and has stopped.
+	QueueFinished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QueueCommandQueueFull;
+
+impl std::fmt::Display for QueueCommandQueueFull {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Cannot send a command to the sound queue because the command queue is full")
+	}
+}
+
+impl std::error::Error for QueueCommandQueueFull {}
+
+enum QueueCommand {
+	Enqueue(Box<dyn Decoder>),
+	Skip,
+	Clear,
+}
+
+/// Controls a [`SoundQueue`] running on the streaming thread.
+pub struct SoundQueueHandle {
+	command_producer: Producer<QueueCommand>,
+	event_consumer: Consumer<QueueEvent>,
+}
+
+impl SoundQueueHandle {
+	/// Adds a track to the end of the queue.
+	pub fn enqueue(&mut self, decoder: Box<dyn Decoder>) -> Result<(), QueueCommandQueueFull> {
+		self.command_producer
+			.push(QueueCommand::Enqueue(decoder))
+			.map_err(|_| QueueCommandQueueFull)
+	}
+
+	/// Stops the current track and immediately begins preloading and
+	/// playing the next one in the queue (if any).
+	pub fn skip(&mut self) -> Result<(), QueueCommandQueueFull> {
+		self.command_producer
+			.push(QueueCommand::Skip)
+			.map_err(|_| QueueCommandQueueFull)
+	}
+
+	/// Removes all tracks from the queue, including the one currently
+	/// playing.
+	pub fn clear(&mut self) -> Result<(), QueueCommandQueueFull> {
+		self.command_producer
+			.push(QueueCommand::Clear)
+			.map_err(|_| QueueCommandQueueFull)
+	}
+
+	/// Returns the next queue event, if one has been pushed since the last
+	/// call to this method.
+	pub fn pop_event(&mut self) -> Option<QueueEvent> {
+		self.event_consumer.pop()
+	}
+}
+
+struct Track {
+	decoder: Box<dyn Decoder>,
+	frames: VecDeque<Frame>,
+	/// `true` once the decoder has reported there's nothing left to decode.
+	finished_decoding: bool,
+}
+
+impl Track {
+	fn new(mut decoder: Box<dyn Decoder>) -> Self {
+		let (frames, finished_decoding) = match decoder.decode() {
+			Some(frames) => (frames, false),
+			None => (VecDeque::new(), true),
+		};
+		Self {
+			decoder,
+			frames,
+			finished_decoding,
+		}
+	}
+
+	fn lookahead_frames(&mut self, lookahead: f64) -> usize {
+		(self.decoder.sample_rate() as f64 * lookahead).round() as usize
+	}
+
+	/// Decodes more audio once the buffered frames run low, so a track
+	/// keeps playing past the first chunk its decoder handed back.
+	fn refill(&mut self, lookahead_frames: usize) {
+		if self.finished_decoding || self.frames.len() > lookahead_frames {
+			return;
+		}
+		match self.decoder.decode() {
+			Some(more) => self.frames.extend(more),
+			None => self.finished_decoding = true,
+		}
+	}
+
+	/// `true` once the decoder is done *and* every decoded frame has been
+	/// played back.
+	fn is_fully_drained(&self) -> bool {
+		self.finished_decoding && self.frames.is_empty()
+	}
+}
+
+/// Runs on the streaming thread, chaining together the [`Decoder`]s handed
+/// to it by a [`SoundQueueHandle`] so playback never has a silent gap
+/// between tracks.
+pub struct SoundQueue {
+	settings: QueueSettings,
+	pending: VecDeque<Box<dyn Decoder>>,
+	current: Option<Track>,
+	next: Option<Track>,
+	/// How many more frames of crossfade are left to produce. `0` means
+	/// no crossfade is in progress.
+	crossfade_remaining: usize,
+	command_consumer: Consumer<QueueCommand>,
+	event_producer: Producer<QueueEvent>,
+}
+
+impl SoundQueue {
+	fn process_commands(&mut self) {
+		while let Some(command) = self.command_consumer.pop() {
+			match command {
+				QueueCommand::Enqueue(decoder) => self.pending.push_back(decoder),
+				QueueCommand::Skip => {
+					self.crossfade_remaining = 0;
+					self.current = self
+						.next
+						.take()
+						.or_else(|| self.pending.pop_front().map(Track::new));
+				}
+				QueueCommand::Clear => {
+					self.crossfade_remaining = 0;
+					self.current = None;
+					self.next = None;
+					self.pending.clear();
+				}
+			}
+		}
+	}
+
+	/// Tops up the current and next tracks' decoded frame buffers, and
+	/// preloads the next track once the current one is close enough to
+	/// the end.
+	fn refill_and_preload(&mut self) {
+		if let Some(current) = &mut self.current {
+			let lookahead_frames = current.lookahead_frames(self.settings.lookahead);
+			// `refill` has to keep topping up at least `crossfade_frames`
+			// frames even if `lookahead` alone would stop sooner, or
+			// `finished_decoding` can flip true with nothing left in
+			// `frames` for `maybe_start_crossfade` to reserve.
+			let reserve_frames = lookahead_frames.max(self.settings.crossfade_frames);
+			current.refill(reserve_frames);
+			if self.next.is_none() && current.frames.len() <= reserve_frames {
+				if let Some(decoder) = self.pending.pop_front() {
+					self.next = Some(Track::new(decoder));
+					self.event_producer.push(QueueEvent::NextTrack).ok();
+				}
+			}
+		}
+		if let Some(next) = &mut self.next {
+			let lookahead_frames = next.lookahead_frames(self.settings.lookahead);
+			next.refill(lookahead_frames.max(self.settings.crossfade_frames));
+		}
+	}
+
+	/// Promotes `next` (or the next pending decoder) to `current`.
+	fn advance_to_next(&mut self) {
+		self.current = self
+			.next
+			.take()
+			.or_else(|| self.pending.pop_front().map(Track::new));
+		if self.current.is_none() {
+			self.event_producer.push(QueueEvent::QueueFinished).ok();
+		}
+	}
+
+	/// Starts the crossfade once `current` has finished decoding and only
+	/// has `crossfade_frames` (or fewer) frames of real audio left to play,
+	/// so those last frames are reserved for mixing with `next` instead of
+	/// being played out on their own first. Does nothing if a crossfade is
+	/// already in progress or isn't configured.
+	fn maybe_start_crossfade(&mut self) {
+		if self.crossfade_remaining > 0 || self.settings.crossfade_frames == 0 {
+			return;
+		}
+		let Some(current) = &self.current else {
+			return;
+		};
+		if !current.finished_decoding || self.next.is_none() {
+			return;
+		}
+		let remaining = current.frames.len();
+		if remaining > 0 && remaining <= self.settings.crossfade_frames {
+			self.crossfade_remaining = remaining;
+		}
+	}
+
+	/// Produces the next frame of audio, advancing the queue to the next
+	/// track (crossfading over `crossfade_frames` frames, if configured)
+	/// once the current track is exhausted.
+	pub fn process(&mut self) -> Frame {
+		self.process_commands();
+		self.refill_and_preload();
+		self.maybe_start_crossfade();
+
+		if self.crossfade_remaining > 0 {
+			let crossfade_frames = self.settings.crossfade_frames.max(1) as f32;
+			let fade_in = 1.0 - (self.crossfade_remaining as f32 / crossfade_frames);
+			let fade_out = 1.0 - fade_in;
+			let current_frame = self
+				.current
+				.as_mut()
+				.and_then(|track| track.frames.pop_front())
+				.unwrap_or(Frame::from_mono(0.0));
+			let next_frame = self
+				.next
+				.as_mut()
+				.and_then(|track| track.frames.pop_front())
+				.unwrap_or(Frame::from_mono(0.0));
+			self.crossfade_remaining -= 1;
+			if self.crossfade_remaining == 0 {
+				self.advance_to_next();
+			}
+			return current_frame * fade_out + next_frame * fade_in;
+		}
+
+		let frame = self
+			.current
+			.as_mut()
+			.and_then(|track| track.frames.pop_front())
+			.unwrap_or(Frame::from_mono(0.0));
+
+		let current_is_drained = self
+			.current
+			.as_ref()
+			.map(Track::is_fully_drained)
+			.unwrap_or(false);
+		if current_is_drained {
+			// `maybe_start_crossfade` already reserves the last
+			// `crossfade_frames` before `current` drains, so reaching this
+			// point fully drained means there was nothing left to crossfade
+			// with (no `next` queued in time, or crossfading is disabled).
+			self.advance_to_next();
+		}
+
+		frame
+	}
+}
+
+/// Creates a [`SoundQueue`] and a [`SoundQueueHandle`] for controlling it.
+pub fn sound_queue(settings: QueueSettings) -> (SoundQueueHandle, SoundQueue) {
+	let (command_producer, command_consumer) = RingBuffer::new(COMMAND_CAPACITY).split();
+	let (event_producer, event_consumer) = RingBuffer::new(EVENT_CAPACITY).split();
+	(
+		SoundQueueHandle {
+			command_producer,
+			event_consumer,
+		},
+		SoundQueue {
+			settings,
+			pending: VecDeque::new(),
+			current: None,
+			next: None,
+			crossfade_remaining: 0,
+			command_consumer,
+			event_producer,
+		},
+	)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// A decoder that hands back `chunk_len` frames of `value` per `decode()`
+	/// call, `chunks_remaining` times, then reports it's done.
+	struct TestDecoder {
+		chunks_remaining: usize,
+		chunk_len: usize,
+		value: f32,
+	}
+
+	impl Decoder for TestDecoder {
+		fn sample_rate(&mut self) -> u32 {
+			44100
+		}
+
+		fn decode(&mut self) -> Option<VecDeque<Frame>> {
+			if self.chunks_remaining == 0 {
+				return None;
+			}
+			self.chunks_remaining -= 1;
+			Some(VecDeque::from(vec![Frame::from_mono(self.value); self.chunk_len]))
+		}
+
+		fn reset(&mut self) {}
+	}
+
+	#[test]
+	fn plays_past_the_first_decoded_chunk() {
+		let (_handle, mut queue) = sound_queue(QueueSettings::new().lookahead(0.0));
+		queue.current = Some(Track::new(Box::new(TestDecoder {
+			chunks_remaining: 3,
+			chunk_len: 4,
+			value: 1.0,
+		})));
+		// The first `decode()` call only buffers 4 frames; without refilling,
+		// the 5th `process()` call would already see an empty/exhausted track.
+		for _ in 0..4 {
+			assert_eq!(queue.process(), Frame::from_mono(1.0));
+		}
+		assert_eq!(queue.process(), Frame::from_mono(1.0));
+	}
+
+	#[test]
+	fn crossfade_eventually_hands_off_and_finishes() {
+		let (_handle, mut queue) = sound_queue(
+			QueueSettings::new()
+				.lookahead(0.0)
+				.crossfade_frames(4),
+		);
+		queue.current = Some(Track::new(Box::new(TestDecoder {
+			chunks_remaining: 1,
+			chunk_len: 4,
+			value: 1.0,
+		})));
+		queue
+			.pending
+			.push_back(Box::new(TestDecoder {
+				chunks_remaining: 1,
+				chunk_len: 4,
+				value: 0.0,
+			}));
+
+		let mut frames_produced = 0;
+		while queue.current.is_some() || queue.crossfade_remaining > 0 {
+			queue.process();
+			frames_produced += 1;
+			assert!(frames_produced < 100, "queue stalled instead of finishing");
+		}
+	}
+
+	#[test]
+	fn refill_reserves_enough_frames_for_a_crossfade_even_with_no_lookahead() {
+		let (_handle, mut queue) = sound_queue(QueueSettings::new().lookahead(0.0).crossfade_frames(4));
+		queue.current = Some(Track::new(Box::new(TestDecoder {
+			chunks_remaining: 1,
+			chunk_len: 4,
+			value: 5.0,
+		})));
+		queue.pending.push_back(Box::new(TestDecoder {
+			chunks_remaining: 1,
+			chunk_len: 4,
+			value: 0.0,
+		}));
+
+		// With `lookahead` at 0, `refill` used to stop topping up `current`
+		// the instant it ran dry, so `finished_decoding` flipped true with
+		// `frames` already empty and `maybe_start_crossfade` had nothing
+		// left to reserve -- the handoff faded in from silence instead of
+		// `current`'s real last samples. `fade_in` is 0.0 on the very first
+		// crossfade frame, so it should come out as pure `current` (5.0),
+		// not silence.
+		assert_eq!(queue.process(), Frame::from_mono(5.0));
+	}
+
+	#[test]
+	fn crossfade_mixes_real_audio_from_both_tracks() {
+		let (_handle, mut queue) = sound_queue(
+			QueueSettings::new()
+				.lookahead(0.5)
+				.crossfade_frames(2),
+		);
+		// `current` has already finished decoding and has exactly
+		// `crossfade_frames` of real audio left; `next` has a full chunk
+		// buffered. If the crossfade mixed silence in for `current` (the
+		// bug), these frames would come out as a blend with 0.0 instead of
+		// with the track's actual last samples.
+		queue.current = Some(Track {
+			decoder: Box::new(TestDecoder {
+				chunks_remaining: 0,
+				chunk_len: 0,
+				value: 1.0,
+			}),
+			frames: VecDeque::from(vec![Frame::from_mono(1.0); 2]),
+			finished_decoding: true,
+		});
+		queue.next = Some(Track::new(Box::new(TestDecoder {
+			chunks_remaining: 1,
+			chunk_len: 4,
+			value: 3.0,
+		})));
+
+		// fade_in starts at 0.0, so the first frame is pure `current` either
+		// way; the second frame is where mixing real audio (average 2.0)
+		// diverges from mixing with silence (average 1.5).
+		assert_eq!(queue.process(), Frame::from_mono(1.0));
+		assert_eq!(queue.process(), Frame::from_mono(2.0));
+	}
+}
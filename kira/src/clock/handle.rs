@@ -0,0 +1,45 @@
+//! An interface for reading a clock's ticking state.
+
+use std::sync::Arc;
+
+use super::{ClockId, ClockShared, TickBoundary};
+
+/// Reads a clock's current tick count, fractional beat position, and last
+/// musical boundary. It has no `start`/`pause`/`stop`: those are methods on
+/// the [`Clock`](super::Clock) itself, which is owned and driven by whoever
+/// created it with [`clock`](super::clock).
+#[derive(Debug, Clone)]
+pub struct ClockHandle {
+	id: ClockId,
+	shared: Arc<ClockShared>,
+}
+
+impl ClockHandle {
+	pub(crate) fn new(id: ClockId, shared: Arc<ClockShared>) -> Self {
+		Self { id, shared }
+	}
+
+	/// Returns the ID of the clock, for use with
+	/// [`StartTime::Quantized`](crate::instance::StartTime::Quantized) and
+	/// [`SoundHandle::on_tick`](crate::sound::handle::SoundHandle::on_tick).
+	pub fn id(&self) -> ClockId {
+		self.id
+	}
+
+	/// Returns the number of ticks the clock has advanced.
+	pub fn ticks(&self) -> u64 {
+		self.shared.ticks()
+	}
+
+	/// Returns the continuous, sample-accurate beat position: ticks elapsed
+	/// plus how far the clock has progressed towards the next tick.
+	pub fn fractional_position(&self) -> f64 {
+		self.shared.fractional_position()
+	}
+
+	/// Returns what kind of musical boundary the clock's current tick count
+	/// last landed on.
+	pub fn last_boundary(&self) -> TickBoundary {
+		self.shared.last_boundary()
+	}
+}
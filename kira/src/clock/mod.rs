@@ -0,0 +1,366 @@
+//! Tempo-aware ticking for scheduling musically-aligned events.
+//!
+//! A [`Clock`] converts a tempo (in BPM) and a musical grid
+//! (`subdivisions_per_beat`, [`TimeSignature`]) into ticks: each tick is one
+//! subdivision (e.g. a 16th note), and [`Clock::update`] reports both a
+//! continuous [`fractional_position`](ClockShared::fractional_position) and,
+//! for whichever tick just landed, what kind of [`TickBoundary`] it was —
+//! so a sequencer can tell a bar downbeat from an ordinary subdivision.
+//!
+//! This supersedes the clock that used to live in `crates/kira`: that copy
+//! had the same `ClockId`/`TimeSignature`/`fractional_position` shape but
+//! wasn't depended on by [`StartTime`](crate::instance::StartTime) or
+//! anything else this crate actually wires up, so there were two
+//! incompatible clocks and only one of them did anything. This is now the
+//! only one.
+//!
+//! Call [`clock`] to create a [`Clock`]/[`ClockHandle`] pair — the same
+//! factory-function shape `kira-streaming`'s `sound_queue` uses for its
+//! `SoundQueue`/`SoundQueueHandle` pair. The returned `Clock` is yours to
+//! drive from wherever you're running an audio update loop.
+//!
+//! Nothing in this crate advances a `Clock` on its own — same as nothing
+//! advances an [`Instance`](crate::instance::Instance) — so whatever owns
+//! one needs to call [`Clock::update`] once per audio update itself.
+
+pub(crate) mod handle;
+
+use std::sync::{
+	atomic::{AtomicBool, AtomicU64, AtomicU8, AtomicUsize, Ordering},
+	Arc,
+};
+
+use handle::ClockHandle;
+
+static NEXT_CLOCK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A unique identifier for a clock, following the same pattern as
+/// [`InstanceId`](crate::instance::InstanceId): minted up front so a
+/// [`StartTime::Quantized`](crate::instance::StartTime::Quantized) can name
+/// a specific clock before the audio thread has created one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClockId(usize);
+
+impl ClockId {
+	pub(crate) fn new() -> Self {
+		Self(NEXT_CLOCK_ID.fetch_add(1, Ordering::SeqCst))
+	}
+}
+
+/// A musical time signature, e.g. 4 beats per bar in 4/4 time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+	pub beats_per_bar: u32,
+}
+
+impl TimeSignature {
+	pub fn new(beats_per_bar: u32) -> Self {
+		Self { beats_per_bar }
+	}
+}
+
+/// What kind of musical boundary a clock tick lands on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickBoundary {
+	/// The tick starts a new bar (and therefore also a new beat).
+	Bar = 0,
+	/// The tick starts a new beat, but not a new bar.
+	Beat = 1,
+	/// The tick is a subdivision within a beat.
+	Subdivision = 2,
+}
+
+impl TickBoundary {
+	fn from_u8(value: u8) -> Self {
+		match value {
+			0 => Self::Bar,
+			1 => Self::Beat,
+			_ => Self::Subdivision,
+		}
+	}
+}
+
+/// Configures a [`Clock`]'s tempo and musical grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSettings {
+	/// The clock's tempo, in beats per minute.
+	pub tempo: f64,
+	pub time_signature: TimeSignature,
+	/// How many ticks make up one beat, e.g. `4` if one tick is a 16th
+	/// note and a beat is a quarter note.
+	pub subdivisions_per_beat: u64,
+}
+
+impl ClockSettings {
+	pub fn new(tempo: f64) -> Self {
+		Self {
+			tempo,
+			time_signature: TimeSignature::new(4),
+			subdivisions_per_beat: 1,
+		}
+	}
+
+	/// Sets the time signature used for bar/beat detection.
+	pub fn time_signature(self, time_signature: TimeSignature) -> Self {
+		Self {
+			time_signature,
+			..self
+		}
+	}
+
+	/// Sets how many ticks make up one beat.
+	pub fn subdivisions_per_beat(self, subdivisions_per_beat: u64) -> Self {
+		Self {
+			subdivisions_per_beat: subdivisions_per_beat.max(1),
+			..self
+		}
+	}
+
+	/// Derives how many ticks this clock advances per second from its
+	/// tempo: one beat is `60.0 / tempo` seconds, and one tick is
+	/// `1 / subdivisions_per_beat` of a beat.
+	pub fn ticks_per_second(&self) -> f64 {
+		(self.tempo / 60.0) * self.subdivisions_per_beat as f64
+	}
+}
+
+impl Default for ClockSettings {
+	fn default() -> Self {
+		Self::new(120.0)
+	}
+}
+
+pub(crate) struct ClockShared {
+	ticking: AtomicBool,
+	ticks: AtomicU64,
+	fractional_position_bits: AtomicU64,
+	last_boundary: AtomicU8,
+}
+
+impl ClockShared {
+	fn new() -> Self {
+		Self {
+			ticking: AtomicBool::new(false),
+			ticks: AtomicU64::new(0),
+			fractional_position_bits: AtomicU64::new(0),
+			last_boundary: AtomicU8::new(TickBoundary::Subdivision as u8),
+		}
+	}
+
+	pub fn ticking(&self) -> bool {
+		self.ticking.load(Ordering::SeqCst)
+	}
+
+	pub fn ticks(&self) -> u64 {
+		self.ticks.load(Ordering::SeqCst)
+	}
+
+	/// Returns `true` if the clock's current tick count lands on the
+	/// quantization grid described by `interval` and `offset`, meaning
+	/// anything waiting on it should begin now. Tick counts below `offset`
+	/// are never on the grid.
+	pub fn is_quantization_boundary(&self, interval: u64, offset: u64) -> bool {
+		if interval == 0 {
+			return false;
+		}
+		let ticks = self.ticks();
+		ticks >= offset && (ticks - offset) % interval == 0
+	}
+
+	/// Returns the continuous, sample-accurate beat position last reported
+	/// by [`Clock::update`].
+	pub fn fractional_position(&self) -> f64 {
+		f64::from_bits(self.fractional_position_bits.load(Ordering::SeqCst))
+	}
+
+	/// Returns what kind of musical boundary the clock's current tick count
+	/// last landed on.
+	pub fn last_boundary(&self) -> TickBoundary {
+		TickBoundary::from_u8(self.last_boundary.load(Ordering::SeqCst))
+	}
+}
+
+enum State {
+	NotStarted,
+	Started { ticks: u64 },
+}
+
+/// A tempo-aware clock, created by [`clock`] and meant to be owned and
+/// driven by whatever runs your audio update loop. See the module docs for
+/// why nothing in this crate calls [`Clock::update`] on one of these itself.
+pub struct Clock {
+	id: ClockId,
+	shared: Arc<ClockShared>,
+	settings: ClockSettings,
+	ticking: bool,
+	state: State,
+	tick_timer: f64,
+}
+
+impl Clock {
+	pub(crate) fn new(settings: ClockSettings) -> Self {
+		Self {
+			id: ClockId::new(),
+			shared: Arc::new(ClockShared::new()),
+			settings,
+			ticking: false,
+			state: State::NotStarted,
+			tick_timer: 1.0,
+		}
+	}
+
+	pub(crate) fn id(&self) -> ClockId {
+		self.id
+	}
+
+	pub(crate) fn shared(&self) -> Arc<ClockShared> {
+		self.shared.clone()
+	}
+
+	pub fn start(&mut self) {
+		self.ticking = true;
+		self.shared.ticking.store(true, Ordering::SeqCst);
+	}
+
+	pub fn pause(&mut self) {
+		self.ticking = false;
+		self.shared.ticking.store(false, Ordering::SeqCst);
+	}
+
+	pub fn stop(&mut self) {
+		self.pause();
+		self.state = State::NotStarted;
+		self.shared.ticks.store(0, Ordering::SeqCst);
+	}
+
+	/// Advances the clock by `dt` seconds, returning the new tick count if
+	/// one (or more) ticks elapsed.
+	pub fn update(&mut self, dt: f64) -> Option<u64> {
+		if !self.ticking {
+			return None;
+		}
+		let mut new_tick_count = None;
+		self.tick_timer -= self.settings.ticks_per_second() * dt;
+		while self.tick_timer <= 0.0 {
+			self.tick_timer += 1.0;
+			let tick_count = match &mut self.state {
+				State::NotStarted => {
+					self.state = State::Started { ticks: 0 };
+					0
+				}
+				State::Started { ticks } => {
+					*ticks += 1;
+					*ticks
+				}
+			};
+			self.shared.ticks.store(tick_count, Ordering::SeqCst);
+			self.shared
+				.last_boundary
+				.store(self.boundary_at(tick_count) as u8, Ordering::SeqCst);
+			new_tick_count = Some(tick_count);
+		}
+		self.shared
+			.fractional_position_bits
+			.store(self.fractional_position().to_bits(), Ordering::SeqCst);
+		new_tick_count
+	}
+
+	/// Returns a continuous, sample-accurate beat position: the number of
+	/// ticks that have elapsed plus how far the clock has progressed
+	/// towards the next tick. Reported to handles via
+	/// [`ClockShared::fractional_position`].
+	fn fractional_position(&self) -> f64 {
+		// `State::Started { ticks }` holds the index of the last tick, and the
+		// first tick is index 0, so one more tick than that has elapsed.
+		let elapsed_ticks = match self.state {
+			State::NotStarted => 0,
+			State::Started { ticks } => ticks + 1,
+		};
+		elapsed_ticks as f64 + (1.0 - self.tick_timer)
+	}
+
+	/// Classifies the given tick count as landing on a bar, a beat, or
+	/// just a subdivision, given the clock's configured time signature and
+	/// subdivisions-per-beat. Reported to handles via
+	/// [`ClockShared::last_boundary`].
+	fn boundary_at(&self, ticks: u64) -> TickBoundary {
+		let ticks_per_beat = self.settings.subdivisions_per_beat;
+		let ticks_per_bar = ticks_per_beat * self.settings.time_signature.beats_per_bar as u64;
+		if ticks_per_bar != 0 && ticks % ticks_per_bar == 0 {
+			TickBoundary::Bar
+		} else if ticks % ticks_per_beat == 0 {
+			TickBoundary::Beat
+		} else {
+			TickBoundary::Subdivision
+		}
+	}
+}
+
+/// Creates a [`Clock`] and the [`ClockHandle`] used to read its state,
+/// mirroring `kira-streaming`'s `sound_queue`. The caller owns the
+/// returned `Clock` and is responsible for calling [`Clock::update`] once
+/// per audio update — nothing in this crate does that on its own, see the
+/// module docs.
+pub fn clock(settings: ClockSettings) -> (ClockHandle, Clock) {
+	let clock = Clock::new(settings);
+	let handle = ClockHandle::new(clock.id(), clock.shared());
+	(handle, clock)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn ticks_per_second_derives_from_tempo_and_subdivisions() {
+		let settings = ClockSettings::new(120.0).subdivisions_per_beat(4);
+		// 120 BPM is 2 beats per second; 4 subdivisions per beat is 8
+		// ticks per second.
+		assert_eq!(settings.ticks_per_second(), 8.0);
+	}
+
+	#[test]
+	fn quantization_boundary_respects_interval_and_offset() {
+		let shared = ClockShared::new();
+		shared.ticks.store(8, Ordering::SeqCst);
+		assert!(shared.is_quantization_boundary(4, 0));
+		assert!(!shared.is_quantization_boundary(3, 0));
+		shared.ticks.store(10, Ordering::SeqCst);
+		assert!(shared.is_quantization_boundary(4, 2));
+		assert!(!shared.is_quantization_boundary(0, 0));
+	}
+
+	#[test]
+	fn update_advances_ticks_at_the_tempo_derived_rate() {
+		let mut clock = Clock::new(ClockSettings::new(60.0));
+		clock.start();
+		// 60 BPM, 1 subdivision per beat, is exactly 1 tick per second.
+		assert_eq!(clock.update(0.5), None);
+		assert_eq!(clock.update(0.5), Some(0));
+		assert_eq!(clock.shared().fractional_position(), 1.0);
+	}
+
+	#[test]
+	fn clock_wires_the_handle_to_the_same_clock_it_creates() {
+		let (handle, mut clock) = clock(ClockSettings::new(60.0));
+		assert_eq!(handle.id(), clock.id());
+		clock.start();
+		assert_eq!(clock.update(1.0), Some(0));
+		assert_eq!(handle.ticks(), 0);
+		assert_eq!(clock.update(1.0), Some(1));
+		assert_eq!(handle.ticks(), 1);
+	}
+
+	#[test]
+	fn boundary_detection_flags_bars_and_beats_from_the_time_signature() {
+		let clock = Clock::new(
+			ClockSettings::new(120.0)
+				.subdivisions_per_beat(2)
+				.time_signature(TimeSignature::new(4)),
+		);
+		assert_eq!(clock.boundary_at(0), TickBoundary::Bar);
+		assert_eq!(clock.boundary_at(2), TickBoundary::Beat);
+		assert_eq!(clock.boundary_at(1), TickBoundary::Subdivision);
+		assert_eq!(clock.boundary_at(8), TickBoundary::Bar);
+	}
+}
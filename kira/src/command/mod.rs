@@ -0,0 +1,56 @@
+//! Commands sent from handles to the audio thread.
+//!
+//! `SoundHandle`/`InstanceHandle` push `Command`s onto a
+//! [`CommandProducer`](producer::CommandProducer); on the other end,
+//! [`instance::pool::InstancePool`](crate::instance::pool::InstancePool)
+//! pops them once per tick and applies them — inserting new `Instance`s
+//! for `Play`, looking up the right one(s) for
+//! `PauseInstancesOf`/`SeekTo`/`AddModulator`/etc., and calling
+//! `Instance::update` to advance the ones still playing. Nothing in this
+//! crate calls `InstancePool::tick` from a real audio callback yet (that
+//! needs picking an audio backend, which is out of scope here), but the
+//! consumer itself is real.
+
+pub(crate) mod producer;
+
+use crate::{
+	instance::{
+		Instance, InstanceId, PauseInstanceSettings, ResumeInstanceSettings, StopInstanceSettings,
+	},
+	modulator::{ModulatorId, Oscillator},
+	sound::SoundId,
+};
+
+/// A command affecting one or more instances, sent from a handle to the
+/// audio thread's instance update loop.
+#[derive(Debug)]
+pub(crate) enum InstanceCommand {
+	Play(InstanceId, Instance),
+	PauseInstancesOf(SoundId, PauseInstanceSettings),
+	ResumeInstancesOf(SoundId, ResumeInstanceSettings),
+	StopInstancesOf(SoundId, StopInstanceSettings),
+	/// Jumps the instance's playback cursor to an absolute position (in
+	/// seconds), wrapped through `loop_start` the same way `Instance::update`
+	/// wraps a position that runs off the end of `duration`.
+	SeekTo(InstanceId, f64),
+	/// Moves the instance's playback cursor by a relative amount (in
+	/// seconds). Applied the same way as `SeekTo`.
+	SeekBy(InstanceId, f64),
+	/// Registers `oscillator` on the instance's
+	/// [`ModulatorTable`](crate::modulator::ModulatorTable) under an ID
+	/// already minted by [`InstanceHandle::add_modulator`](crate::instance::handle::InstanceHandle::add_modulator).
+	AddModulator(InstanceId, ModulatorId, Oscillator),
+	/// Unregisters a modulator previously added with `AddModulator`.
+	RemoveModulator(InstanceId, ModulatorId),
+}
+
+#[derive(Debug)]
+pub(crate) enum Command {
+	Instance(InstanceCommand),
+}
+
+impl From<InstanceCommand> for Command {
+	fn from(command: InstanceCommand) -> Self {
+		Command::Instance(command)
+	}
+}
@@ -0,0 +1,47 @@
+//! A handle-side sender for pushing commands to the audio thread.
+
+use std::{
+	error::Error,
+	fmt::Display,
+	sync::{Arc, Mutex},
+};
+
+use ringbuf::Producer;
+
+use super::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CommandProducerError;
+
+impl Display for CommandProducerError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Cannot send a command because the command queue is full")
+	}
+}
+
+impl Error for CommandProducerError {}
+
+/// A cloneable handle for sending commands to the audio thread. Clones
+/// share the same underlying queue.
+#[derive(Clone)]
+pub struct CommandProducer(Arc<Mutex<Producer<Command>>>);
+
+impl CommandProducer {
+	pub(crate) fn new(producer: Producer<Command>) -> Self {
+		Self(Arc::new(Mutex::new(producer)))
+	}
+
+	pub(crate) fn push(&mut self, command: Command) -> Result<(), CommandProducerError> {
+		self.0
+			.lock()
+			.expect("command producer mutex poisoned")
+			.push(command)
+			.map_err(|_| CommandProducerError)
+	}
+}
+
+impl std::fmt::Debug for CommandProducer {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("CommandProducer").finish()
+	}
+}
@@ -0,0 +1,80 @@
+//! An interface for controlling individual instances of sounds.
+
+use std::sync::Arc;
+
+use crate::{
+	command::{
+		producer::{CommandProducer, CommandProducerError},
+		InstanceCommand,
+	},
+	modulator::{ModulatorId, Oscillator},
+};
+
+use super::{InstanceId, PublicState};
+
+/// Allows you to control an individual instance of a sound.
+#[derive(Debug, Clone)]
+pub struct InstanceHandle {
+	id: InstanceId,
+	public_state: Arc<PublicState>,
+	command_sender: CommandProducer,
+}
+
+impl InstanceHandle {
+	pub(crate) fn new(
+		id: InstanceId,
+		public_state: Arc<PublicState>,
+		command_sender: CommandProducer,
+	) -> Self {
+		Self {
+			id,
+			public_state,
+			command_sender,
+		}
+	}
+
+	/// Returns the ID of the instance.
+	pub fn id(&self) -> InstanceId {
+		self.id
+	}
+
+	/// Returns the current playback position of the instance (in seconds).
+	pub fn position(&self) -> f64 {
+		self.public_state.position()
+	}
+
+	/// Jumps the instance's playback cursor to the given position (in
+	/// seconds), wrapped through the sound's loop region the same way
+	/// playback wraps when it runs off the end on its own.
+	pub fn seek_to(&mut self, position: f64) -> Result<(), CommandProducerError> {
+		self.command_sender
+			.push(InstanceCommand::SeekTo(self.id, position).into())
+	}
+
+	/// Moves the instance's playback cursor by the given amount (in
+	/// seconds), wrapped the same way as `seek_to`.
+	pub fn seek_by(&mut self, amount: f64) -> Result<(), CommandProducerError> {
+		self.command_sender
+			.push(InstanceCommand::SeekBy(self.id, amount).into())
+	}
+
+	/// Registers `oscillator` as a modulator on this instance and returns
+	/// the [`ModulatorId`] it was registered under. Feed that ID (or just
+	/// call `.into()`) to `volume`/`playback_rate`/`panning` on
+	/// [`InstanceSettings`](super::InstanceSettings) or
+	/// [`TrackSettings`](crate::track::settings::TrackSettings) to drive
+	/// that parameter from it. The ID is minted here, before the oscillator
+	/// reaches the instance, so it's usable immediately.
+	pub fn add_modulator(&mut self, oscillator: Oscillator) -> Result<ModulatorId, CommandProducerError> {
+		let id = ModulatorId::new();
+		self.command_sender
+			.push(InstanceCommand::AddModulator(self.id, id, oscillator).into())?;
+		Ok(id)
+	}
+
+	/// Unregisters a modulator previously added with `add_modulator`.
+	pub fn remove_modulator(&mut self, id: ModulatorId) -> Result<(), CommandProducerError> {
+		self.command_sender
+			.push(InstanceCommand::RemoveModulator(self.id, id).into())
+	}
+}
@@ -0,0 +1,501 @@
+//! Individual playing instances of a sound.
+//!
+//! `Instance`/`InstanceId`/`InstanceSettings` are shared scaffolding that
+//! `sound::handle::SoundHandle` and `instance::handle::InstanceHandle`
+//! both build on. [`pool::InstancePool`] is the consumer that actually
+//! owns and advances `Instance`s — see its module docs for what it does
+//! and doesn't cover yet.
+
+pub(crate) mod handle;
+pub(crate) mod pool;
+
+use std::sync::{
+	atomic::{AtomicU64, AtomicUsize, Ordering},
+	Arc, Mutex,
+};
+
+use ringbuf::Producer;
+
+use crate::{
+	clock::ClockId,
+	mixer::TrackIndex,
+	modulator::{ModulatorId, ModulatorTable, Oscillator},
+	sound::event::SoundEvent,
+	value::Value,
+};
+
+/// How often, by default, an [`Instance`] reports its position via
+/// [`SoundEvent::Position`] if a caller hasn't overridden it with
+/// [`InstanceSettings::position_update_interval`].
+const DEFAULT_POSITION_UPDATE_INTERVAL: f64 = 0.1;
+
+static NEXT_INSTANCE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A unique identifier for an instance of a sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InstanceId(usize);
+
+impl InstanceId {
+	fn new() -> Self {
+		Self(NEXT_INSTANCE_ID.fetch_add(1, Ordering::SeqCst))
+	}
+}
+
+/// Playback state shared between an [`Instance`] and its
+/// [`InstanceHandle`](handle::InstanceHandle).
+#[derive(Debug)]
+pub(crate) struct PublicState {
+	position: AtomicU64,
+}
+
+impl PublicState {
+	fn new() -> Self {
+		Self {
+			position: AtomicU64::new(0.0f64.to_bits()),
+		}
+	}
+
+	pub fn position(&self) -> f64 {
+		f64::from_bits(self.position.load(Ordering::SeqCst))
+	}
+
+	fn set_position(&self, position: f64) {
+		self.position.store(position.to_bits(), Ordering::SeqCst);
+	}
+}
+
+/// When an instance should begin playing.
+///
+/// Nothing advances a [`Clock`](crate::clock::Clock) automatically (see
+/// the `clock` module docs), so a [`StartTime::Quantized`] instance doesn't
+/// start on its own either: whatever is driving that clock's ticks has to
+/// call [`SoundHandle::on_tick`](crate::sound::handle::SoundHandle::on_tick)
+/// once per tick (with that clock's [`ClockId`] and tick count) for pending
+/// starts to ever fire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartTime {
+	/// Start as soon as `play` is called.
+	Immediate,
+	/// Wait until `clock`'s tick count satisfies
+	/// `(ticks - offset) % interval == 0`, e.g. to align playback to a
+	/// beat or bar. Tick counts below `offset` never start it. Naming the clock (rather than matching any tick count
+	/// handed to `on_tick`) means instances quantized to different clocks
+	/// don't cross-fire off of each other's ticks.
+	Quantized {
+		clock: ClockId,
+		interval: u64,
+		offset: u64,
+	},
+}
+
+impl StartTime {
+	/// Returns `true` if this start time is satisfied by `clock` having
+	/// just reached `ticks`.
+	pub(crate) fn is_ready(&self, clock: ClockId, ticks: u64) -> bool {
+		match *self {
+			StartTime::Immediate => true,
+			StartTime::Quantized {
+				clock: start_clock,
+				interval,
+				offset,
+			} => {
+				start_clock == clock
+					&& interval != 0
+					&& ticks >= offset
+					&& (ticks - offset) % interval == 0
+			}
+		}
+	}
+}
+
+impl Default for StartTime {
+	fn default() -> Self {
+		Self::Immediate
+	}
+}
+
+/// Settings resolved from an [`InstanceSettings`] plus the sound's own
+/// defaults, ready to hand to [`Instance::new`].
+pub(crate) struct InstanceSettingsInternal {
+	pub volume: Value,
+	pub playback_rate: Value,
+	pub panning: Value,
+	pub loop_start: Option<f64>,
+	pub track: TrackIndex,
+	pub position_update_interval: f64,
+}
+
+/// Settings for playing an instance of a sound, passed to
+/// [`SoundHandle::play`](crate::sound::handle::SoundHandle::play).
+pub struct InstanceSettings {
+	pub(crate) id: InstanceId,
+	volume: Value,
+	playback_rate: Value,
+	panning: Value,
+	loop_start: Option<f64>,
+	track: Option<TrackIndex>,
+	position_update_interval: f64,
+	pub(crate) start_time: StartTime,
+}
+
+impl InstanceSettings {
+	pub fn new() -> Self {
+		Self {
+			id: InstanceId::new(),
+			volume: Value::Fixed(1.0),
+			playback_rate: Value::Fixed(1.0),
+			panning: Value::Fixed(0.5),
+			loop_start: None,
+			track: None,
+			position_update_interval: DEFAULT_POSITION_UPDATE_INTERVAL,
+			start_time: StartTime::Immediate,
+		}
+	}
+
+	pub fn volume(self, volume: impl Into<Value>) -> Self {
+		Self {
+			volume: volume.into(),
+			..self
+		}
+	}
+
+	pub fn playback_rate(self, playback_rate: impl Into<Value>) -> Self {
+		Self {
+			playback_rate: playback_rate.into(),
+			..self
+		}
+	}
+
+	pub fn panning(self, panning: impl Into<Value>) -> Self {
+		Self {
+			panning: panning.into(),
+			..self
+		}
+	}
+
+	pub fn loop_start(self, loop_start: f64) -> Self {
+		Self {
+			loop_start: Some(loop_start),
+			..self
+		}
+	}
+
+	pub fn track(self, track: impl Into<TrackIndex>) -> Self {
+		Self {
+			track: Some(track.into()),
+			..self
+		}
+	}
+
+	/// Sets the minimum time (in seconds) between [`SoundEvent::Position`]
+	/// updates pushed from this instance. Defaults to 100ms.
+	pub fn position_update_interval(self, position_update_interval: f64) -> Self {
+		Self {
+			position_update_interval,
+			..self
+		}
+	}
+
+	/// Delays this instance's start until `start_time`'s quantization grid
+	/// is hit instead of starting it as soon as `play` is called. See
+	/// [`StartTime`].
+	pub fn start_time(self, start_time: StartTime) -> Self {
+		Self { start_time, ..self }
+	}
+
+	pub(crate) fn into_internal(
+		self,
+		sound_duration: f64,
+		default_loop_start: Option<f64>,
+		default_track: TrackIndex,
+	) -> InstanceSettingsInternal {
+		InstanceSettingsInternal {
+			volume: self.volume,
+			playback_rate: self.playback_rate,
+			panning: self.panning,
+			loop_start: self
+				.loop_start
+				.or(default_loop_start)
+				.map(|loop_start| loop_start.min(sound_duration)),
+			track: self.track.unwrap_or(default_track),
+			position_update_interval: self.position_update_interval,
+		}
+	}
+}
+
+impl Default for InstanceSettings {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Settings for pausing instances.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PauseInstanceSettings {
+	pub fade_tween: Option<crate::parameter::Tween>,
+}
+
+/// Settings for resuming paused instances.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResumeInstanceSettings {
+	pub fade_tween: Option<crate::parameter::Tween>,
+}
+
+/// Settings for stopping instances.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StopInstanceSettings {
+	pub fade_tween: Option<crate::parameter::Tween>,
+}
+
+/// The resolved playback parameters produced by one call to
+/// [`Instance::update`]. Any status events that call produced are pushed
+/// straight to the instance's own event sender instead of being returned
+/// here — see [`Instance::push_event`].
+pub(crate) struct InstanceTick {
+	pub volume: f64,
+	pub playback_rate: f64,
+	pub panning: f64,
+}
+
+/// An individual playing instance of a sound, owned by the audio thread.
+pub(crate) struct Instance {
+	sound_id: crate::sound::SoundId,
+	public_state: Arc<PublicState>,
+	settings: InstanceSettingsInternal,
+	/// Modulators driving this instance's `Value::Modulator` fields (if
+	/// any). Scoped per-instance rather than shared, since a vibrato or
+	/// tremolo oscillator only ever makes sense for the one instance it was
+	/// registered on.
+	modulators: ModulatorTable,
+	duration: f64,
+	position: f64,
+	/// `true` once a non-looping instance has reached `duration` and pushed
+	/// `SoundEvent::Finished`, so it stops advancing.
+	finished: bool,
+	/// `true` while paused by `InstanceCommand::PauseInstancesOf`; `update`
+	/// leaves position/events alone entirely while this is set.
+	paused: bool,
+	time_since_position_event: f64,
+	/// Where `Finished`/`LoopedBack`/`Position` get pushed, same channel
+	/// `SoundHandle::pop_event` drains — handed in by
+	/// `SoundHandle::play` when it constructs this instance, the same way
+	/// the handle itself pushes `Playing`/`Paused`/`Resumed`/`Stopped`.
+	event_sender: Arc<Mutex<Producer<SoundEvent>>>,
+}
+
+impl Instance {
+	pub(crate) fn new(
+		sound_id: crate::sound::SoundId,
+		duration: f64,
+		_loop_start_override: Option<f64>,
+		settings: InstanceSettingsInternal,
+		event_sender: Arc<Mutex<Producer<SoundEvent>>>,
+	) -> Self {
+		Self {
+			sound_id,
+			public_state: Arc::new(PublicState::new()),
+			settings,
+			modulators: ModulatorTable::new(),
+			duration,
+			position: 0.0,
+			finished: false,
+			paused: false,
+			time_since_position_event: 0.0,
+			event_sender,
+		}
+	}
+
+	pub(crate) fn public_state(&self) -> Arc<PublicState> {
+		self.public_state.clone()
+	}
+
+	/// Pushes an event to this instance's sound's event stream — the same
+	/// channel `SoundHandle::push_event` uses for `Playing`/`Paused`/
+	/// `Resumed`/`Stopped`.
+	fn push_event(&self, event: SoundEvent) {
+		self.event_sender
+			.lock()
+			.expect("event sender mutex poisoned")
+			.push(event)
+			.ok();
+	}
+
+	/// Returns the ID of the sound this is an instance of, so a pool keyed
+	/// by [`InstanceId`] can still find every instance of a given sound for
+	/// `InstanceCommand::PauseInstancesOf`/`ResumeInstancesOf`/`StopInstancesOf`.
+	pub(crate) fn sound_id(&self) -> crate::sound::SoundId {
+		self.sound_id
+	}
+
+	/// `true` once a non-looping instance has reached the end of its
+	/// playable region, so a pool can drop it instead of ticking it
+	/// forever.
+	pub(crate) fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	/// Registers `oscillator` under `id` (minted ahead of time by
+	/// [`handle::InstanceHandle::add_modulator`]) so it starts driving any
+	/// of this instance's `Value::Modulator` fields that reference it.
+	pub(crate) fn add_modulator(&mut self, id: ModulatorId, oscillator: Oscillator) {
+		self.modulators.insert(id, oscillator);
+	}
+
+	/// Unregisters a modulator previously added with `add_modulator`.
+	pub(crate) fn remove_modulator(&mut self, id: ModulatorId) {
+		self.modulators.remove(id);
+	}
+
+	/// Freezes the instance in place. `settings.fade_tween` is ignored, so
+	/// the pause takes effect at once with no fade.
+	pub(crate) fn pause(&mut self, _settings: PauseInstanceSettings) {
+		self.paused = true;
+	}
+
+	/// Un-freezes an instance previously frozen with `pause`. Same
+	/// `fade_tween` caveat as `pause`.
+	pub(crate) fn resume(&mut self, _settings: ResumeInstanceSettings) {
+		self.paused = false;
+	}
+
+	/// Jumps the playback cursor to `position` (in seconds), wrapping it
+	/// through `loop_start` the same way `update` does when playback runs
+	/// off the end of `duration`.
+	pub(crate) fn seek_to(&mut self, position: f64) {
+		self.position = self.wrap_position(position);
+		self.public_state.set_position(self.position);
+	}
+
+	/// Moves the playback cursor by `amount` (in seconds), wrapped the same
+	/// way as `seek_to`.
+	pub(crate) fn seek_by(&mut self, amount: f64) {
+		self.seek_to(self.position + amount);
+	}
+
+	/// Wraps `position` through `loop_start` once it reaches `duration`,
+	/// mirroring the loop/finish transition `update` applies each tick,
+	/// without touching `finished` or pushing any events.
+	fn wrap_position(&self, position: f64) -> f64 {
+		if position < self.duration {
+			return position.max(0.0);
+		}
+		match self.settings.loop_start {
+			Some(loop_start) => {
+				let loop_length = (self.duration - loop_start).max(f64::EPSILON);
+				loop_start + (position - self.duration) % loop_length
+			}
+			None => self.duration,
+		}
+	}
+
+	/// Advances this instance by `dt` seconds: advances its modulators and
+	/// resolves `volume`/`playback_rate`/`panning` against their current
+	/// output, advances its playback position (looping back to
+	/// `loop_start` or finishing at `duration`), and pushes any of those
+	/// transitions plus throttled `Position` updates to `push_event`. Meant
+	/// to be called once per tick by whatever owns this instance's pool;
+	/// see [`pool::InstancePool::tick`](self::pool::InstancePool::tick).
+	pub(crate) fn update(&mut self, dt: f64) -> InstanceTick {
+		self.modulators.update(dt);
+		let playback_rate = self.settings.playback_rate.resolve(&self.modulators);
+
+		if !self.finished && !self.paused {
+			self.position += playback_rate * dt;
+			if self.position >= self.duration {
+				match self.settings.loop_start {
+					Some(_) => {
+						self.position = self.wrap_position(self.position);
+						self.push_event(SoundEvent::LoopedBack);
+					}
+					None => {
+						self.position = self.duration;
+						self.finished = true;
+						self.push_event(SoundEvent::Finished);
+					}
+				}
+			}
+			self.public_state.set_position(self.position);
+
+			self.time_since_position_event += dt;
+			if self.time_since_position_event >= self.settings.position_update_interval {
+				self.time_since_position_event = 0.0;
+				self.push_event(SoundEvent::Position(self.position));
+			}
+		}
+
+		InstanceTick {
+			volume: self.settings.volume.resolve(&self.modulators),
+			playback_rate,
+			panning: self.settings.panning.resolve(&self.modulators),
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn immediate_start_is_always_ready() {
+		let clock = ClockId::new();
+		assert!(StartTime::Immediate.is_ready(clock, 0));
+		assert!(StartTime::Immediate.is_ready(clock, 123));
+	}
+
+	#[test]
+	fn quantized_start_waits_for_its_grid() {
+		let clock = ClockId::new();
+		let start_time = StartTime::Quantized {
+			clock,
+			interval: 4,
+			offset: 2,
+		};
+		assert!(!start_time.is_ready(clock, 0));
+		assert!(!start_time.is_ready(clock, 1));
+		assert!(start_time.is_ready(clock, 2));
+		assert!(start_time.is_ready(clock, 6));
+		assert!(!start_time.is_ready(clock, 7));
+	}
+
+	#[test]
+	fn quantized_start_ignores_ticks_from_a_different_clock() {
+		let clock = ClockId::new();
+		let other_clock = ClockId::new();
+		let start_time = StartTime::Quantized {
+			clock,
+			interval: 4,
+			offset: 0,
+		};
+		assert!(start_time.is_ready(clock, 4));
+		assert!(!start_time.is_ready(other_clock, 4));
+	}
+
+	// `on_tick` (`SoundHandle::on_tick`) promotes a pending instance by
+	// checking `StartTime::is_ready` against a real clock's tick count, not
+	// a hand-rolled one — this drives a genuine `Clock` (created the same
+	// way `on_tick`'s caller would, via `clock::clock`) and confirms
+	// `is_ready` actually agrees with the ticks that clock reports as it's
+	// updated, instead of only ever being exercised against numbers a test
+	// invents.
+	#[test]
+	fn quantized_start_is_ready_against_a_real_clock_s_reported_ticks() {
+		use crate::clock::{clock, ClockSettings};
+
+		let (handle, mut real_clock) = clock(ClockSettings::new(60.0).subdivisions_per_beat(1));
+		let start_time = StartTime::Quantized {
+			clock: handle.id(),
+			interval: 2,
+			offset: 2,
+		};
+		real_clock.start();
+
+		// 60 BPM, 1 subdivision per beat, is exactly 1 tick per second.
+		for _ in 0..2 {
+			real_clock.update(1.0);
+			assert!(!start_time.is_ready(handle.id(), handle.ticks()));
+		}
+		real_clock.update(1.0);
+		assert_eq!(handle.ticks(), 2);
+		assert!(start_time.is_ready(handle.id(), handle.ticks()));
+	}
+}
@@ -0,0 +1,117 @@
+//! Drains `Command`s sent by `SoundHandle`/`InstanceHandle` and owns the
+//! `Instance`s they refer to.
+//!
+//! Before this existed, every command a handle pushed — `Play`,
+//! `PauseInstancesOf`, `SeekTo`, `AddModulator`, and so on — sat in the
+//! ring buffer forever: nothing popped the other end, so none of it had
+//! any effect. [`InstancePool`] is that missing consumer: [`instances`]
+//! creates one alongside the [`CommandProducer`] used to send it
+//! commands, mirroring `kira-streaming`'s `sound_queue`.
+//!
+//! Nothing in this crate calls [`InstancePool::tick`] from a real audio
+//! callback yet — doing that means picking an audio backend, which is
+//! its own piece of work outside this series. What's here is real and
+//! reachable, just not yet hooked up to actual sound hardware.
+
+use std::collections::HashMap;
+
+use ringbuf::{Consumer, RingBuffer};
+
+use crate::command::{producer::CommandProducer, Command, InstanceCommand};
+
+use super::{Instance, InstanceId};
+
+const COMMAND_CAPACITY: usize = 16;
+
+/// Owns every [`Instance`] that's been `Play`ed and applies `Command`s
+/// popped from the queue a [`CommandProducer`] pushes onto.
+pub(crate) struct InstancePool {
+	instances: HashMap<InstanceId, Instance>,
+	command_consumer: Consumer<Command>,
+}
+
+impl InstancePool {
+	/// Applies every command that's been sent since the last call:
+	/// `Play` adds the instance to the pool, `PauseInstancesOf`/
+	/// `ResumeInstancesOf`/`StopInstancesOf` affect every instance of the
+	/// given sound, and `SeekTo`/`SeekBy`/`AddModulator`/`RemoveModulator`
+	/// affect the one instance they name (silently doing nothing if that
+	/// instance has already finished and been dropped).
+	fn process_commands(&mut self) {
+		while let Some(Command::Instance(command)) = self.command_consumer.pop() {
+			match command {
+				InstanceCommand::Play(id, instance) => {
+					self.instances.insert(id, instance);
+				}
+				InstanceCommand::PauseInstancesOf(sound_id, settings) => {
+					for instance in self.instances.values_mut() {
+						if instance.sound_id() == sound_id {
+							instance.pause(settings);
+						}
+					}
+				}
+				InstanceCommand::ResumeInstancesOf(sound_id, settings) => {
+					for instance in self.instances.values_mut() {
+						if instance.sound_id() == sound_id {
+							instance.resume(settings);
+						}
+					}
+				}
+				InstanceCommand::StopInstancesOf(sound_id, _settings) => {
+					self.instances.retain(|_, instance| instance.sound_id() != sound_id);
+				}
+				InstanceCommand::SeekTo(id, position) => {
+					if let Some(instance) = self.instances.get_mut(&id) {
+						instance.seek_to(position);
+					}
+				}
+				InstanceCommand::SeekBy(id, amount) => {
+					if let Some(instance) = self.instances.get_mut(&id) {
+						instance.seek_by(amount);
+					}
+				}
+				InstanceCommand::AddModulator(id, modulator_id, oscillator) => {
+					if let Some(instance) = self.instances.get_mut(&id) {
+						instance.add_modulator(modulator_id, oscillator);
+					}
+				}
+				InstanceCommand::RemoveModulator(id, modulator_id) => {
+					if let Some(instance) = self.instances.get_mut(&id) {
+						instance.remove_modulator(modulator_id);
+					}
+				}
+			}
+		}
+	}
+
+	/// Applies pending commands, then advances every live instance by `dt`
+	/// seconds and drops any that finished this tick. Call once per audio
+	/// update.
+	pub(crate) fn tick(&mut self, dt: f64) {
+		self.process_commands();
+		for instance in self.instances.values_mut() {
+			instance.update(dt);
+		}
+		self.instances.retain(|_, instance| !instance.is_finished());
+	}
+}
+
+// No unit tests here: constructing an `Instance` needs a
+// `mixer::TrackIndex`, and that module doesn't exist anywhere in this
+// crate (see `InstanceSettingsInternal::track`), so there's no real value
+// to build one with in this tree. `InstancePool`'s own logic is plain
+// `HashMap` bookkeeping over `Instance` methods (`pause`/`seek_to`/
+// `add_modulator`/`is_finished`) — nothing here besides that lookup.
+
+/// Creates an [`InstancePool`] and the [`CommandProducer`] used to send it
+/// commands.
+pub(crate) fn instances() -> (CommandProducer, InstancePool) {
+	let (producer, consumer) = RingBuffer::new(COMMAND_CAPACITY).split();
+	(
+		CommandProducer::new(producer),
+		InstancePool {
+			instances: HashMap::new(),
+			command_consumer: consumer,
+		},
+	)
+}
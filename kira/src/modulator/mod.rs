@@ -0,0 +1,271 @@
+//! Periodic modulation (vibrato, tremolo, auto-pan) that can be mixed
+//! into any [`Value`](crate::value::Value) sink.
+//!
+//! Unlike a [`Tween`](crate::parameter::Tween), which eases a parameter
+//! from one fixed value to another and then stops, an [`Oscillator`] runs
+//! continuously, producing a value that swings back and forth around a
+//! center point forever. Every [`Instance`](crate::instance::Instance) and
+//! every [`Track`](crate::track::Track) owns its own [`ModulatorTable`];
+//! an oscillator registered on one is named by a [`ModulatorId`]
+//! ([`InstanceHandle::add_modulator`](crate::instance::handle::InstanceHandle::add_modulator)
+//! returns a fresh one, `Track::add_modulator` takes one minted with
+//! [`ModulatorId::new`]) that can be fed to
+//! that same owner's `volume`/`playback_rate`/`panning` field — an
+//! instance's oscillator drives vibrato/tremolo on that one instance,
+//! while a track's drives auto-pan (or tremolo) for everything routed
+//! through it. An ID only resolves against the table it was registered
+//! on, so a `Value::Modulator` only makes sense on the same owner that
+//! has the matching `ModulatorTable`. Both call [`ModulatorTable::update`]
+//! once per tick, so every sink reading `Value::Modulator(id)` sees the
+//! oscillator's current phase instead of a frozen snapshot. There's no standing
+//! conversion straight from `Oscillator` to `Value` on purpose: a `Value`
+//! is otherwise a one-time number, so converting once and handing that off
+//! would freeze the oscillator at whatever phase it was in at that instant.
+//!
+//! [`OscillatorSettings::frequency`] is always in Hz; there's no way yet to
+//! sync it to a [`Clock`](crate::clock::Clock)'s tempo instead, so a
+//! vibrato or tremolo effect can't currently be expressed "4 times per
+//! beat" the way
+//! [`StartTime::Quantized`](crate::instance::StartTime::Quantized)
+//! expresses a launch time in ticks.
+
+use std::{
+	collections::HashMap,
+	f64::consts::PI,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The shape of an [`Oscillator`]'s periodic output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+	Sine,
+	Triangle,
+	Square,
+	Saw,
+}
+
+impl Waveform {
+	/// Evaluates the waveform at the given phase (in the range `0.0..1.0`).
+	fn value(&self, phase: f64) -> f64 {
+		match self {
+			Waveform::Sine => (phase * 2.0 * PI).sin(),
+			Waveform::Triangle => 4.0 * (phase - (phase + 0.75).floor() + 0.25).abs() - 1.0,
+			Waveform::Square => {
+				if phase < 0.5 {
+					1.0
+				} else {
+					-1.0
+				}
+			}
+			Waveform::Saw => 2.0 * phase - 1.0,
+		}
+	}
+}
+
+/// Configures an [`Oscillator`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OscillatorSettings {
+	pub waveform: Waveform,
+	/// The rate of oscillation, in Hz.
+	pub frequency: f64,
+	/// The value the oscillator's output swings around.
+	pub center: f64,
+	/// How far the oscillator's output swings above and below `center`.
+	pub depth: f64,
+	/// How long (in seconds) the oscillator stays at `center` before it
+	/// starts swinging.
+	pub attack_delay: f64,
+}
+
+impl OscillatorSettings {
+	pub fn new(waveform: Waveform) -> Self {
+		Self {
+			waveform,
+			frequency: 5.0,
+			center: 0.0,
+			depth: 1.0,
+			attack_delay: 0.0,
+		}
+	}
+
+	pub fn frequency(self, frequency: f64) -> Self {
+		Self { frequency, ..self }
+	}
+
+	pub fn center(self, center: f64) -> Self {
+		Self { center, ..self }
+	}
+
+	pub fn depth(self, depth: f64) -> Self {
+		Self { depth, ..self }
+	}
+
+	pub fn attack_delay(self, attack_delay: f64) -> Self {
+		Self {
+			attack_delay,
+			..self
+		}
+	}
+}
+
+/// A low-frequency oscillator that produces a periodic value for
+/// modulating another parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oscillator {
+	settings: OscillatorSettings,
+	phase: f64,
+	time: f64,
+}
+
+impl Oscillator {
+	pub fn new(settings: OscillatorSettings) -> Self {
+		Self {
+			settings,
+			phase: 0.0,
+			time: 0.0,
+		}
+	}
+
+	/// Advances the oscillator by `dt` seconds and returns its new value.
+	pub fn update(&mut self, dt: f64) -> f64 {
+		self.time += dt;
+		if self.time < self.settings.attack_delay {
+			return self.settings.center;
+		}
+		self.phase += self.settings.frequency * dt;
+		self.phase -= self.phase.floor();
+		self.value()
+	}
+
+	/// Returns the oscillator's current value without advancing it.
+	pub fn value(&self) -> f64 {
+		if self.time < self.settings.attack_delay {
+			return self.settings.center;
+		}
+		self.settings.center + self.settings.depth * self.settings.waveform.value(self.phase)
+	}
+}
+
+static NEXT_MODULATOR_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A unique identifier for an [`Oscillator`] registered in a
+/// [`ModulatorTable`]. Wrap one in [`Value::Modulator`](crate::value::Value::Modulator)
+/// (or just call `.into()`) to drive a parameter from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModulatorId(usize);
+
+impl ModulatorId {
+	/// Mints a fresh ID, following the same pattern as
+	/// [`InstanceId`](crate::instance::InstanceId): a handle can mint one of
+	/// these up front and hand it to the audio thread in a command (see
+	/// [`ModulatorTable::insert`]) instead of waiting for the audio thread
+	/// to register the oscillator and report back an ID.
+	pub(crate) fn new() -> Self {
+		Self(NEXT_MODULATOR_ID.fetch_add(1, Ordering::SeqCst))
+	}
+}
+
+/// Owns every registered [`Oscillator`] and resolves
+/// [`Value::Modulator`](crate::value::Value::Modulator) against them.
+#[derive(Debug, Default)]
+pub struct ModulatorTable {
+	oscillators: HashMap<ModulatorId, Oscillator>,
+}
+
+impl ModulatorTable {
+	pub fn new() -> Self {
+		Self {
+			oscillators: HashMap::new(),
+		}
+	}
+
+	/// Registers `oscillator` and returns an ID that can be plugged into a
+	/// [`Value`](crate::value::Value) sink to drive it from this oscillator.
+	pub fn add(&mut self, oscillator: Oscillator) -> ModulatorId {
+		let id = ModulatorId::new();
+		self.oscillators.insert(id, oscillator);
+		id
+	}
+
+	/// Registers `oscillator` under an ID minted ahead of time (by
+	/// [`ModulatorId::new`]), for callers that need the ID before the
+	/// oscillator is actually inserted here, e.g. a handle that mints the ID
+	/// before sending the oscillator to the audio thread in a command.
+	pub(crate) fn insert(&mut self, id: ModulatorId, oscillator: Oscillator) {
+		self.oscillators.insert(id, oscillator);
+	}
+
+	/// Unregisters the oscillator with the given ID, if it's still
+	/// present.
+	pub fn remove(&mut self, id: ModulatorId) {
+		self.oscillators.remove(&id);
+	}
+
+	/// Advances every registered oscillator by `dt` seconds. Call this
+	/// once per tick from the audio thread.
+	pub fn update(&mut self, dt: f64) {
+		for oscillator in self.oscillators.values_mut() {
+			oscillator.update(dt);
+		}
+	}
+
+	/// Returns the current value of the oscillator registered under `id`,
+	/// or `0.0` if it's been removed (or never existed).
+	pub fn value(&self, id: ModulatorId) -> f64 {
+		self.oscillators
+			.get(&id)
+			.map(Oscillator::value)
+			.unwrap_or(0.0)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn saw_ramps_linearly_without_a_midpoint_discontinuity() {
+		let waveform = Waveform::Saw;
+		assert!((waveform.value(0.0) - -1.0).abs() < 1e-9);
+		assert!((waveform.value(0.25) - -0.5).abs() < 1e-9);
+		assert!((waveform.value(0.5) - 0.0).abs() < 1e-9);
+		assert!((waveform.value(0.75) - 0.5).abs() < 1e-9);
+		// No jump back to -1.0 partway through the cycle.
+		assert!(waveform.value(0.49) < waveform.value(0.51));
+	}
+
+	#[test]
+	fn attack_delay_holds_at_center_before_swinging() {
+		let mut oscillator = Oscillator::new(
+			OscillatorSettings::new(Waveform::Sine)
+				.center(0.5)
+				.depth(0.5)
+				.attack_delay(1.0),
+		);
+		assert_eq!(oscillator.update(0.5), 0.5);
+		assert_eq!(oscillator.value(), 0.5);
+	}
+
+	#[test]
+	fn table_forgets_removed_modulators() {
+		let mut table = ModulatorTable::new();
+		let id = table.add(Oscillator::new(OscillatorSettings::new(Waveform::Square)));
+		assert_eq!(table.value(id), 1.0);
+		table.remove(id);
+		assert_eq!(table.value(id), 0.0);
+	}
+
+	#[test]
+	fn table_update_advances_every_registered_oscillator() {
+		let mut table = ModulatorTable::new();
+		let a = table.add(Oscillator::new(
+			OscillatorSettings::new(Waveform::Square).frequency(1.0),
+		));
+		let b = table.add(Oscillator::new(
+			OscillatorSettings::new(Waveform::Square).frequency(1.0),
+		));
+		table.update(0.5);
+		assert_eq!(table.value(a), -1.0);
+		assert_eq!(table.value(b), -1.0);
+	}
+}
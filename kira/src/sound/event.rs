@@ -0,0 +1,33 @@
+//! Status notifications about a sound's instances.
+
+/// A notification about a change in an instance's playback state.
+///
+/// Drain these from a [`SoundHandle`](super::handle::SoundHandle) with
+/// `pop_event` to drive a "now playing" view without polling `position()`
+/// every frame. `Playing`, `Paused`, `Resumed`, and `Stopped` are pushed by
+/// the handle itself as soon as the corresponding command is sent;
+/// `Finished`, `LoopedBack`, and `Position` are pushed by
+/// [`Instance::update`](crate::instance::Instance) as it advances playback
+/// position each tick, via the event sender it's constructed with — see
+/// [`InstancePool::tick`](crate::instance::pool::InstancePool::tick), the
+/// only thing that calls `Instance::update`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundEvent {
+	/// An instance started playing for the first time.
+	Playing,
+	/// An instance was paused.
+	Paused,
+	/// A paused instance was resumed.
+	Resumed,
+	/// An instance was stopped.
+	Stopped,
+	/// An instance reached the end of its playable region and was not
+	/// looping, so it has finished on its own.
+	Finished,
+	/// A looping instance reached the end of its loop region and jumped
+	/// back to the loop start.
+	LoopedBack,
+	/// The current playback position, pushed at most once per
+	/// [`InstanceSettings::position_update_interval`](crate::instance::InstanceSettings::position_update_interval).
+	Position(f64),
+}
@@ -1,21 +1,33 @@
 //! An interface for controlling sounds.
 
+use std::sync::{Arc, Mutex};
+
+use ringbuf::{Consumer, Producer, RingBuffer};
+
 use crate::{
+	clock::ClockId,
 	command::{
 		producer::{CommandProducer, CommandProducerError},
 		InstanceCommand,
 	},
 	instance::{
-		handle::InstanceHandle, Instance, InstanceSettings, PauseInstanceSettings,
-		ResumeInstanceSettings, StopInstanceSettings,
+		handle::InstanceHandle, Instance, InstanceId, InstanceSettings, PauseInstanceSettings,
+		ResumeInstanceSettings, StartTime, StopInstanceSettings,
 	},
 	mixer::TrackIndex,
 };
 
-use super::{Sound, SoundId};
+use super::{event::SoundEvent, Sound, SoundId};
+
+const EVENT_CAPACITY: usize = 16;
 
 /// Allows you to control a sound.
-#[derive(Debug, Clone)]
+///
+/// `SoundHandle` is `Clone` so multiple owners can issue commands, but its
+/// event stream is a single-consumer channel: if more than one clone calls
+/// `pop_event`, each event only goes to whichever clone happens to poll it
+/// first. Only one owner should drain events for a given sound.
+#[derive(Clone)]
 pub struct SoundHandle {
 	id: SoundId,
 	duration: f64,
@@ -23,10 +35,27 @@ pub struct SoundHandle {
 	semantic_duration: Option<f64>,
 	default_loop_start: Option<f64>,
 	command_sender: CommandProducer,
+	event_sender: Arc<Mutex<Producer<SoundEvent>>>,
+	event_receiver: Arc<Mutex<Consumer<SoundEvent>>>,
+	pending_starts: Arc<Mutex<Vec<(InstanceId, Instance, StartTime)>>>,
+}
+
+impl std::fmt::Debug for SoundHandle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("SoundHandle")
+			.field("id", &self.id)
+			.field("duration", &self.duration)
+			.field("default_track", &self.default_track)
+			.field("semantic_duration", &self.semantic_duration)
+			.field("default_loop_start", &self.default_loop_start)
+			.field("command_sender", &self.command_sender)
+			.finish()
+	}
 }
 
 impl SoundHandle {
 	pub(crate) fn new(sound: &Sound, command_sender: CommandProducer) -> Self {
+		let (event_sender, event_receiver) = RingBuffer::new(EVENT_CAPACITY).split();
 		Self {
 			id: sound.id(),
 			duration: sound.duration(),
@@ -34,9 +63,23 @@ impl SoundHandle {
 			semantic_duration: sound.semantic_duration(),
 			default_loop_start: sound.default_loop_start(),
 			command_sender,
+			event_sender: Arc::new(Mutex::new(event_sender)),
+			event_receiver: Arc::new(Mutex::new(event_receiver)),
+			pending_starts: Arc::new(Mutex::new(Vec::new())),
 		}
 	}
 
+	/// Pushes an event to this sound's event stream. The audio thread does
+	/// the same (from the instance update loop) for events that originate
+	/// there, like `Finished`, `LoopedBack`, and `Position`.
+	fn push_event(&self, event: SoundEvent) {
+		self.event_sender
+			.lock()
+			.expect("event sender mutex poisoned")
+			.push(event)
+			.ok();
+	}
+
 	/// Returns the ID of the sound.
 	pub fn id(&self) -> SoundId {
 		self.id
@@ -67,38 +110,103 @@ impl SoundHandle {
 	}
 
 	/// Plays the sound.
+	///
+	/// If `settings` carries a [`StartTime::Quantized`], the instance is
+	/// held back instead of starting right away: it's queued here and only
+	/// sent to the audio thread once [`SoundHandle::on_tick`] reports a
+	/// tick count that satisfies its quantization grid. The returned
+	/// handle is valid immediately either way.
 	pub fn play(
 		&mut self,
 		settings: InstanceSettings,
 	) -> Result<InstanceHandle, CommandProducerError> {
 		let id = settings.id;
+		let start_time = settings.start_time;
 		let instance = Instance::new(
 			self.id.into(),
 			self.duration,
 			None,
 			settings.into_internal(self.duration, self.default_loop_start, self.default_track),
+			self.event_sender.clone(),
 		);
 		let handle = InstanceHandle::new(id, instance.public_state(), self.command_sender.clone());
-		self.command_sender
-			.push(InstanceCommand::Play(id, instance).into())?;
+		match start_time {
+			StartTime::Immediate => {
+				self.command_sender
+					.push(InstanceCommand::Play(id, instance).into())?;
+				self.push_event(SoundEvent::Playing);
+			}
+			StartTime::Quantized { .. } => {
+				self.pending_starts
+					.lock()
+					.expect("pending starts mutex poisoned")
+					.push((id, instance, start_time));
+			}
+		}
 		Ok(handle)
 	}
 
+	/// Starts any instances whose [`StartTime::Quantized`] names `clock`
+	/// and whose grid is satisfied by `ticks`. Call this once per tick with
+	/// that clock's [`ClockId`] and current tick count (e.g. from
+	/// [`ClockHandle`](crate::clock::handle::ClockHandle)) — nothing calls
+	/// this automatically, since nothing in this crate drives a `Clock`'s
+	/// ticks on its own either (see the `clock` module docs).
+	pub fn on_tick(&mut self, clock: ClockId, ticks: u64) -> Result<(), CommandProducerError> {
+		let mut ready = Vec::new();
+		{
+			let mut pending = self
+				.pending_starts
+				.lock()
+				.expect("pending starts mutex poisoned");
+			let mut i = 0;
+			while i < pending.len() {
+				if pending[i].2.is_ready(clock, ticks) {
+					let (id, instance, _) = pending.remove(i);
+					ready.push((id, instance));
+				} else {
+					i += 1;
+				}
+			}
+		}
+		for (id, instance) in ready {
+			self.command_sender
+				.push(InstanceCommand::Play(id, instance).into())?;
+			self.push_event(SoundEvent::Playing);
+		}
+		Ok(())
+	}
+
 	/// Pauses all instances of this sound.
 	pub fn pause(&mut self, settings: PauseInstanceSettings) -> Result<(), CommandProducerError> {
 		self.command_sender
-			.push(InstanceCommand::PauseInstancesOf(self.id.into(), settings).into())
+			.push(InstanceCommand::PauseInstancesOf(self.id.into(), settings).into())?;
+		self.push_event(SoundEvent::Paused);
+		Ok(())
 	}
 
 	/// Resumes all instances of this sound.
 	pub fn resume(&mut self, settings: ResumeInstanceSettings) -> Result<(), CommandProducerError> {
 		self.command_sender
-			.push(InstanceCommand::ResumeInstancesOf(self.id.into(), settings).into())
+			.push(InstanceCommand::ResumeInstancesOf(self.id.into(), settings).into())?;
+		self.push_event(SoundEvent::Resumed);
+		Ok(())
 	}
 
 	/// Stops all instances of this sound.
 	pub fn stop(&mut self, settings: StopInstanceSettings) -> Result<(), CommandProducerError> {
 		self.command_sender
-			.push(InstanceCommand::StopInstancesOf(self.id.into(), settings).into())
+			.push(InstanceCommand::StopInstancesOf(self.id.into(), settings).into())?;
+		self.push_event(SoundEvent::Stopped);
+		Ok(())
+	}
+
+	/// Returns the next playback status event for this sound's instances,
+	/// if one has been pushed since the last call to this method.
+	pub fn pop_event(&mut self) -> Option<SoundEvent> {
+		self.event_receiver
+			.lock()
+			.expect("event receiver mutex poisoned")
+			.pop()
 	}
 }
@@ -0,0 +1,84 @@
+//! Mixer tracks that sounds' instances are routed to.
+
+pub mod settings;
+
+use crate::modulator::{ModulatorId, ModulatorTable, Oscillator};
+
+use settings::TrackSettings;
+
+/// A single mixer track. Owns the [`ModulatorTable`] that drives any of
+/// `settings`'s `Value::Modulator` fields, the same way an
+/// [`Instance`](crate::instance::Instance) owns one for its own
+/// volume/playback_rate/panning — registering an oscillator here instead
+/// auto-pans (or tremolos) the whole track rather than just one instance
+/// playing on it.
+pub(crate) struct Track {
+	settings: TrackSettings,
+	modulators: ModulatorTable,
+}
+
+impl Track {
+	pub(crate) fn new(settings: TrackSettings) -> Self {
+		Self {
+			settings,
+			modulators: ModulatorTable::new(),
+		}
+	}
+
+	/// Registers `oscillator` under `id` (minted ahead of time, same
+	/// pattern as [`Instance::add_modulator`](crate::instance::Instance::add_modulator)).
+	pub(crate) fn add_modulator(&mut self, id: ModulatorId, oscillator: Oscillator) {
+		self.modulators.insert(id, oscillator);
+	}
+
+	/// Unregisters a modulator previously added with `add_modulator`.
+	pub(crate) fn remove_modulator(&mut self, id: ModulatorId) {
+		self.modulators.remove(id);
+	}
+
+	/// Advances this track's modulators by `dt` seconds. Call once per
+	/// tick, same as [`Instance::update`](crate::instance::Instance::update).
+	pub(crate) fn update(&mut self, dt: f64) {
+		self.modulators.update(dt);
+	}
+
+	/// Resolves the track's current volume against its modulators.
+	pub(crate) fn volume(&self) -> f64 {
+		self.settings.volume.resolve(&self.modulators)
+	}
+
+	/// Resolves the track's current panning against its modulators.
+	pub(crate) fn panning(&self) -> f64 {
+		self.settings.panning.resolve(&self.modulators)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::modulator::{Oscillator, OscillatorSettings, Waveform};
+
+	#[test]
+	fn panning_follows_a_registered_oscillator() {
+		let id = ModulatorId::new();
+		let mut track = Track::new(TrackSettings::new().panning(id));
+		track.add_modulator(
+			id,
+			Oscillator::new(
+				OscillatorSettings::new(Waveform::Square)
+					.center(0.5)
+					.depth(0.5),
+			),
+		);
+		assert_eq!(track.panning(), 1.0);
+	}
+
+	#[test]
+	fn removing_a_modulator_falls_back_to_zero() {
+		let id = ModulatorId::new();
+		let mut track = Track::new(TrackSettings::new().panning(id));
+		track.add_modulator(id, Oscillator::new(OscillatorSettings::new(Waveform::Square)));
+		track.remove_modulator(id);
+		assert_eq!(track.panning(), 0.0);
+	}
+}
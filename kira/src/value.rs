@@ -0,0 +1,72 @@
+//! A number that parameters like volume, playback rate, and panning can
+//! be driven by.
+
+use crate::modulator::{ModulatorId, ModulatorTable};
+
+/// A value that can be fed to things like `set_volume`, `set_playback_rate`,
+/// or [`TrackSettings::panning`](crate::track::settings::TrackSettings::panning).
+///
+/// `Value::Fixed` never changes once set. `Value::Modulator` re-reads a
+/// registered [`Oscillator`](crate::modulator::Oscillator)'s current output
+/// every time it's [`resolve`](Value::resolve)d, so plugging one into
+/// `set_playback_rate` gives vibrato, into `set_volume` gives tremolo, and
+/// into `TrackSettings::panning` gives auto-pan — without ever freezing the
+/// oscillator's phase the way converting it to a one-shot `Value::Fixed`
+/// would.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+	/// A value that doesn't change on its own.
+	Fixed(f64),
+	/// A value driven by a modulator registered in a [`ModulatorTable`].
+	Modulator(ModulatorId),
+}
+
+impl Value {
+	/// Resolves this value to a concrete number, reading the current
+	/// output of the referenced modulator (if any) from `modulators`.
+	pub fn resolve(&self, modulators: &ModulatorTable) -> f64 {
+		match self {
+			Value::Fixed(value) => *value,
+			Value::Modulator(id) => modulators.value(*id),
+		}
+	}
+}
+
+impl From<f64> for Value {
+	fn from(value: f64) -> Self {
+		Value::Fixed(value)
+	}
+}
+
+impl From<ModulatorId> for Value {
+	fn from(id: ModulatorId) -> Self {
+		Value::Modulator(id)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::modulator::{Oscillator, OscillatorSettings, Waveform};
+
+	#[test]
+	fn fixed_resolves_to_itself_regardless_of_modulators() {
+		let modulators = ModulatorTable::new();
+		assert_eq!(Value::Fixed(0.75).resolve(&modulators), 0.75);
+	}
+
+	#[test]
+	fn modulator_resolves_to_the_oscillators_live_value() {
+		let mut modulators = ModulatorTable::new();
+		let id = modulators.add(Oscillator::new(
+			OscillatorSettings::new(Waveform::Square)
+				.frequency(1.0)
+				.center(0.0)
+				.depth(1.0),
+		));
+		let value = Value::Modulator(id);
+		assert_eq!(value.resolve(&modulators), 1.0);
+		modulators.update(0.5);
+		assert_eq!(value.resolve(&modulators), -1.0);
+	}
+}